@@ -0,0 +1,115 @@
+use std::fs;
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::ThreeVector;
+
+// Scale/offset correspond to near-identity corrections (~1x, ~0 offset); a
+// coefficient this large is almost certainly a typo. `apply` uses checked
+// Decimal arithmetic and emits 0.0 for a sample whose correction overflows.
+fn max_coefficient() -> Decimal {
+    Decimal::from(1_000_000)
+}
+
+// value = raw * scale + offset. Coefficients are fixed-precision decimals
+// rather than floats so a calibration file round-trips exactly.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct AxisCalibration {
+    pub scale: Decimal,
+    pub offset: Decimal,
+}
+
+impl Default for AxisCalibration {
+    fn default() -> Self {
+        AxisCalibration {
+            scale: Decimal::from(1),
+            offset: Decimal::from(0),
+        }
+    }
+}
+
+impl AxisCalibration {
+    pub fn validate(&self) -> Result<(), String> {
+        let max = max_coefficient();
+        if self.scale.abs() > max {
+            return Err(format!(
+                "scale {} is out of the allowed range (+/- {})",
+                self.scale, max
+            ));
+        }
+        if self.offset.abs() > max {
+            return Err(format!(
+                "offset {} is out of the allowed range (+/- {})",
+                self.offset, max
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, raw: f32) -> f32 {
+        let raw = Decimal::from_f32_retain(raw).unwrap_or_default();
+        raw.checked_mul(self.scale)
+            .and_then(|scaled| scaled.checked_add(self.offset))
+            .and_then(|result| result.to_f32())
+            .unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct ThreeAxisCalibration {
+    #[serde(default)]
+    pub x: AxisCalibration,
+    #[serde(default)]
+    pub y: AxisCalibration,
+    #[serde(default)]
+    pub z: AxisCalibration,
+}
+
+impl ThreeAxisCalibration {
+    pub fn validate(&self) -> Result<(), String> {
+        self.x.validate()?;
+        self.y.validate()?;
+        self.z.validate()?;
+        Ok(())
+    }
+
+    pub fn apply(&self, raw: &ThreeVector) -> ThreeVector {
+        ThreeVector {
+            x: self.x.apply(raw.x),
+            y: self.y.apply(raw.y),
+            z: self.z.apply(raw.z),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Calibration {
+    #[serde(default)]
+    pub gyro: ThreeAxisCalibration,
+    #[serde(default)]
+    pub acc: ThreeAxisCalibration,
+    #[serde(default)]
+    pub temp: AxisCalibration,
+}
+
+impl Calibration {
+    pub fn validate(&self) -> Result<(), String> {
+        self.gyro.validate()?;
+        self.acc.validate()?;
+        self.temp.validate()?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Calibration {
+        let contents = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("Failed to read calibration file {}: {}", path, e));
+        let calibration: Calibration = serde_json::from_str(&contents)
+            .unwrap_or_else(|e| panic!("Failed to parse calibration file {}: {}", path, e));
+        calibration
+            .validate()
+            .unwrap_or_else(|e| panic!("Invalid calibration file {}: {}", path, e));
+        calibration
+    }
+}
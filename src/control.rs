@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use linux_embedded_hal::{Delay, I2cdev};
+use mpu6050::{Mpu6050, Steps};
+use serde::{Deserialize, Serialize};
+
+use crate::ThreeVector;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "command")]
+pub enum Command {
+    Recalibrate { steps: u8 },
+    GetBias,
+    GetVariance,
+    SetRate { hz: f32 },
+    Ping,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "reply")]
+pub enum Reply {
+    Recalibrated,
+    Bias { bias: ThreeVector },
+    Variance { variance: ThreeVector },
+    RateSet { hz: f32 },
+    Pong,
+    Error { message: String },
+}
+
+pub fn handle(
+    command: Command,
+    mpu: &mut Mpu6050<I2cdev, Delay>,
+    rate_hz: &mut Option<f32>,
+) -> Reply {
+    match command {
+        Command::Recalibrate { steps } => {
+            let calibrated = mpu
+                .soft_calib(Steps(steps))
+                .and_then(|_| mpu.calc_variance(Steps(steps)));
+            match calibrated {
+                Ok(_) => Reply::Recalibrated,
+                Err(_) => Reply::Error {
+                    message: "recalibration failed".to_string(),
+                },
+            }
+        }
+        Command::GetBias => match mpu.get_bias() {
+            Some(bias) => Reply::Bias {
+                bias: ThreeVector {
+                    x: bias.x,
+                    y: bias.y,
+                    z: bias.z,
+                },
+            },
+            None => Reply::Error {
+                message: "bias not yet calibrated".to_string(),
+            },
+        },
+        Command::GetVariance => match mpu.get_variance() {
+            Some(variance) => Reply::Variance {
+                variance: ThreeVector {
+                    x: variance.x,
+                    y: variance.y,
+                    z: variance.z,
+                },
+            },
+            None => Reply::Error {
+                message: "variance not yet calculated".to_string(),
+            },
+        },
+        Command::SetRate { hz } => {
+            if !(hz > 0.0) || Duration::try_from_secs_f32(1.0 / hz).is_err() {
+                return Reply::Error {
+                    message: format!(
+                        "rate must be a positive number of Hz with a representable period, got {}",
+                        hz
+                    ),
+                };
+            }
+            *rate_hz = Some(hz);
+            Reply::RateSet { hz }
+        }
+        Command::Ping => Reply::Pong,
+    }
+}
+
+pub fn poll(
+    socket: &std::net::UdpSocket,
+    mpu: &mut Mpu6050<I2cdev, Delay>,
+    rate_hz: &mut Option<f32>,
+) {
+    let mut buf = [0u8; 256];
+    loop {
+        let (len, sender) = match socket.recv_from(&mut buf) {
+            Ok(result) => result,
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => return,
+            Err(_) => return,
+        };
+
+        let reply = match serde_json::from_slice::<Command>(&buf[..len]) {
+            Ok(command) => handle(command, mpu, rate_hz),
+            Err(e) => Reply::Error {
+                message: format!("malformed command: {}", e),
+            },
+        };
+
+        if let Ok(reply_bytes) = serde_json::to_vec(&reply) {
+            let _ = socket.send_to(&reply_bytes, sender);
+        }
+    }
+}
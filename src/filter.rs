@@ -0,0 +1,103 @@
+use std::time::Instant;
+
+use crate::ThreeVector;
+
+pub struct ComplementaryFilter {
+    alpha: f32,
+    roll: f32,
+    pitch: f32,
+    last_update: Instant,
+}
+
+impl ComplementaryFilter {
+    pub fn new(alpha: f32) -> Self {
+        ComplementaryFilter {
+            alpha,
+            roll: 0.0,
+            pitch: 0.0,
+            last_update: Instant::now(),
+        }
+    }
+
+    pub fn update(&mut self, gyro: &ThreeVector, acc: &ThreeVector) -> (f32, f32) {
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        self.update_with_dt(gyro, acc, dt)
+    }
+
+    // get_gyro returns deg/s; roll_acc/pitch_acc are radians from atan2, so the
+    // gyro term has to be converted before the two can be blended. Split out from
+    // `update` so the blend math can be tested without depending on Instant/wall time.
+    fn update_with_dt(&mut self, gyro: &ThreeVector, acc: &ThreeVector, dt: f32) -> (f32, f32) {
+        let roll_acc = acc.y.atan2(acc.z);
+        let pitch_acc = (-acc.x).atan2((acc.y * acc.y + acc.z * acc.z).sqrt());
+
+        self.roll = self.alpha * (self.roll + gyro.x.to_radians() * dt)
+            + (1.0 - self.alpha) * roll_acc;
+        self.pitch = self.alpha * (self.pitch + gyro.y.to_radians() * dt)
+            + (1.0 - self.alpha) * pitch_acc;
+
+        (self.roll, self.pitch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn vec3(x: f32, y: f32, z: f32) -> ThreeVector {
+        ThreeVector { x, y, z }
+    }
+
+    #[test]
+    fn gyro_rate_is_integrated_as_degrees_per_second() {
+        // alpha = 1.0 isolates the gyro-integration term from the accel correction.
+        let mut filter = ComplementaryFilter::new(1.0);
+        let gyro = vec3(90.0, -90.0, 0.0); // deg/s
+        let acc = vec3(0.0, 0.0, 1.0);
+
+        let (roll, pitch) = filter.update_with_dt(&gyro, &acc, 1.0);
+
+        assert!(
+            (roll - FRAC_PI_2).abs() < EPSILON,
+            "expected roll ~= pi/2 rad after integrating 90 deg/s for 1s, got {}",
+            roll
+        );
+        assert!(
+            (pitch + FRAC_PI_2).abs() < EPSILON,
+            "expected pitch ~= -pi/2 rad after integrating -90 deg/s for 1s, got {}",
+            pitch
+        );
+    }
+
+    #[test]
+    fn alpha_zero_tracks_accel_angle_exactly() {
+        // alpha = 0.0 isolates the accel-derived term from the gyro integration.
+        let mut filter = ComplementaryFilter::new(0.0);
+        let gyro = vec3(1000.0, 1000.0, 0.0); // should have no effect
+        let acc = vec3(0.0, 1.0, 1.0);
+
+        let (roll, pitch) = filter.update_with_dt(&gyro, &acc, 1.0);
+
+        assert!((roll - acc.y.atan2(acc.z)).abs() < EPSILON);
+        assert!((pitch - (-acc.x).atan2((acc.y * acc.y + acc.z * acc.z).sqrt())).abs() < EPSILON);
+    }
+
+    #[test]
+    fn blends_gyro_and_accel_terms_by_alpha() {
+        let alpha = 0.5;
+        let mut filter = ComplementaryFilter::new(alpha);
+        let gyro = vec3(90.0, 0.0, 0.0); // deg/s
+        let acc = vec3(0.0, 1.0, 0.0); // roll_acc = atan2(1, 0) = pi/2
+
+        let (roll, _) = filter.update_with_dt(&gyro, &acc, 1.0);
+
+        let expected = alpha * (0.0 + 90.0f32.to_radians()) + (1.0 - alpha) * 1.0f32.atan2(0.0);
+        assert!((roll - expected).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+// The discriminant doubles as the one-byte tag prefixed to every datagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json = 0,
+    #[cfg(feature = "msgpack")]
+    MsgPack = 1,
+    #[cfg(feature = "cbor")]
+    Cbor = 2,
+    #[cfg(feature = "bincode")]
+    Bincode = 3,
+    #[cfg(feature = "postcard")]
+    Postcard = 4,
+}
+
+impl Format {
+    pub fn encode<T: Serialize>(self, value: &T) -> Vec<u8> {
+        let mut buf = vec![self as u8];
+        match self {
+            Format::Json => buf.extend(serde_json::to_vec(value).unwrap()),
+            #[cfg(feature = "msgpack")]
+            Format::MsgPack => buf.extend(rmp_serde::to_vec(value).unwrap()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => buf.extend(serde_cbor::to_vec(value).unwrap()),
+            #[cfg(feature = "bincode")]
+            Format::Bincode => buf.extend(bincode::serialize(value).unwrap()),
+            #[cfg(feature = "postcard")]
+            Format::Postcard => buf.extend(postcard::to_allocvec(value).unwrap()),
+        }
+        buf
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Ok(Format::MsgPack),
+            #[cfg(feature = "cbor")]
+            "cbor" => Ok(Format::Cbor),
+            #[cfg(feature = "bincode")]
+            "bincode" => Ok(Format::Bincode),
+            #[cfg(feature = "postcard")]
+            "postcard" => Ok(Format::Postcard),
+            other => Err(format!("unknown format: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Format::Json => "json",
+            #[cfg(feature = "msgpack")]
+            Format::MsgPack => "msgpack",
+            #[cfg(feature = "cbor")]
+            Format::Cbor => "cbor",
+            #[cfg(feature = "bincode")]
+            Format::Bincode => "bincode",
+            #[cfg(feature = "postcard")]
+            Format::Postcard => "postcard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        a: u32,
+        b: f32,
+        c: String,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            a: 7,
+            b: 1.5,
+            c: "telemetry".to_string(),
+        }
+    }
+
+    fn decode(format: Format, bytes: &[u8]) -> Sample {
+        assert_eq!(bytes[0], format as u8);
+        let body = &bytes[1..];
+        match format {
+            Format::Json => serde_json::from_slice(body).unwrap(),
+            #[cfg(feature = "msgpack")]
+            Format::MsgPack => rmp_serde::from_slice(body).unwrap(),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => serde_cbor::from_slice(body).unwrap(),
+            #[cfg(feature = "bincode")]
+            Format::Bincode => bincode::deserialize(body).unwrap(),
+            #[cfg(feature = "postcard")]
+            Format::Postcard => postcard::from_bytes(body).unwrap(),
+        }
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let encoded = Format::Json.encode(&sample());
+        assert_eq!(decode(Format::Json, &encoded), sample());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_round_trips() {
+        let encoded = Format::MsgPack.encode(&sample());
+        assert_eq!(decode(Format::MsgPack, &encoded), sample());
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_round_trips() {
+        let encoded = Format::Cbor.encode(&sample());
+        assert_eq!(decode(Format::Cbor, &encoded), sample());
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn bincode_round_trips() {
+        let encoded = Format::Bincode.encode(&sample());
+        assert_eq!(decode(Format::Bincode, &encoded), sample());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_round_trips() {
+        let encoded = Format::Postcard.encode(&sample());
+        assert_eq!(decode(Format::Postcard, &encoded), sample());
+    }
+}
@@ -1,16 +1,28 @@
+mod calibration;
+mod control;
+mod filter;
+mod format;
+mod sequence;
+mod stun;
+
 use std::net::{SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
 
 use clap::{App, Arg};
 use i2cdev::linux::LinuxI2CError;
 use linux_embedded_hal::{Delay, I2cdev};
 use mpu6050::{Mpu6050, Mpu6050Error, Steps};
 use serde::{Deserialize, Serialize};
-use serde_json;
+
+use calibration::Calibration;
+use filter::ComplementaryFilter;
+use format::Format;
+use sequence::SequenceClock;
 
 type Result<T> = std::result::Result<T, Mpu6050Error<LinuxI2CError>>;
 
 #[derive(Debug, Deserialize, Serialize)]
-struct ThreeVector {
+pub struct ThreeVector {
     x: f32,
     y: f32,
     z: f32,
@@ -18,6 +30,8 @@ struct ThreeVector {
 
 #[derive(Debug, Deserialize, Serialize)]
 struct Measurement {
+    seq: u64,
+    time: u64,
     roll: f32,
     pitch: f32,
     temp: f32,
@@ -26,11 +40,14 @@ struct Measurement {
 }
 
 impl Measurement {
-    fn new(mpu: &mut Mpu6050<I2cdev, Delay>, steps: Option<u8>) -> Result<Measurement> {
-        let rp = match steps {
-            Some(steps) => mpu.get_acc_angles_avg(Steps(steps))?,
-            None => mpu.get_acc_angles()?,
-        };
+    fn new(
+        mpu: &mut Mpu6050<I2cdev, Delay>,
+        steps: Option<u8>,
+        filter: &mut ComplementaryFilter,
+        calibration: &Calibration,
+        clock: &mut SequenceClock,
+    ) -> Result<Measurement> {
+        let (seq, time) = clock.next();
 
         let temp = match steps {
             Some(steps) => mpu.get_temp_avg(Steps(steps))?,
@@ -47,20 +64,22 @@ impl Measurement {
             None => mpu.get_acc()?,
         };
 
-        let roll = rp.x;
-        let pitch = rp.y;
-        let gyro = ThreeVector {
+        let gyro = calibration.gyro.apply(&ThreeVector {
             x: gyro.x,
             y: gyro.y,
             z: gyro.z,
-        };
-        let acc = ThreeVector {
+        });
+        let acc = calibration.acc.apply(&ThreeVector {
             x: acc.x,
             y: acc.y,
             z: acc.z,
-        };
+        });
+        let temp = calibration.temp.apply(temp);
+        let (roll, pitch) = filter.update(&gyro, &acc);
 
         Ok(Measurement {
+            seq,
+            time,
             roll,
             pitch,
             temp,
@@ -80,6 +99,58 @@ fn main() -> Result<()> {
                 .required(true)
                 .help("UDP endpoint to log data to."),
         )
+        .arg(
+            Arg::with_name("alpha")
+                .short("a")
+                .long("alpha")
+                .takes_value(true)
+                .default_value("0.98")
+                .help("Complementary filter gain applied to the gyro-integrated angle."),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .default_value("json")
+                .validator(|v| v.parse::<Format>().map(|_| ()))
+                .help("Wire encoding for UDP datagrams."),
+        )
+        .arg(
+            Arg::with_name("calibration")
+                .short("c")
+                .long("calibration")
+                .takes_value(true)
+                .help("Path to a JSON file of per-axis {scale, offset} corrections."),
+        )
+        .arg(
+            Arg::with_name("rate")
+                .short("r")
+                .long("rate")
+                .takes_value(true)
+                .validator(|v| match v.parse::<f32>() {
+                    Ok(hz) if hz > 0.0 && Duration::try_from_secs_f32(1.0 / hz).is_ok() => Ok(()),
+                    Ok(hz) => Err(format!(
+                        "--rate must be a positive number of Hz with a representable period, got {}",
+                        hz
+                    )),
+                    Err(e) => Err(e.to_string()),
+                })
+                .help("Sample rate in Hz to pace the loop to. Unset runs as fast as the bus allows."),
+        )
+        .arg(
+            Arg::with_name("epoch")
+                .short("e")
+                .long("epoch")
+                .takes_value(false)
+                .help("Stamp `time` as Unix epoch microseconds instead of time since start."),
+        )
+        .arg(
+            Arg::with_name("stun")
+                .long("stun")
+                .takes_value(true)
+                .help("STUN server (IP:PORT) to discover this node's public address through."),
+        )
         .get_matches();
 
     let udp_addr: SocketAddrV4 = matches
@@ -88,8 +159,42 @@ fn main() -> Result<()> {
         .parse()
         .expect("--udp value must be IP:PORT");
 
+    let alpha: f32 = matches
+        .value_of("alpha")
+        .unwrap()
+        .parse()
+        .expect("--alpha value must be a float");
+
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let calibration = match matches.value_of("calibration") {
+        Some(path) => Calibration::load(path),
+        None => Calibration::default(),
+    };
+
+    let initial_rate_hz: Option<f32> = matches
+        .value_of("rate")
+        .map(|rate| rate.parse().expect("--rate value must be a float"));
+
+    let epoch = matches.is_present("epoch");
+
+    let stun_server: Option<SocketAddrV4> = matches
+        .value_of("stun")
+        .map(|addr| addr.parse().expect("--stun value must be IP:PORT"));
+
     let udp_sender = UdpSocket::bind("0.0.0.0:0").unwrap();
 
+    if let Some(stun_server) = stun_server {
+        match stun::discover_public_addr(&udp_sender, stun_server) {
+            Ok(public_addr) => println!("Discovered public address via STUN: {}", public_addr),
+            Err(e) => println!("STUN discovery failed: {}", e),
+        }
+    }
+
+    udp_sender
+        .set_nonblocking(true)
+        .expect("Failed to set UDP socket non-blocking");
+
     let i2c = I2cdev::new("/dev/i2c-1").map_err(Mpu6050Error::I2c)?;
     let delay = Delay;
     let mut mpu = Mpu6050::new(i2c, delay);
@@ -102,12 +207,31 @@ fn main() -> Result<()> {
     println!("Calculated variance: {:?}", mpu.get_variance().unwrap());
     println!("");
     println!("Logging sensor measurements to UDP address: {}", udp_addr);
+    println!("Encoding datagrams as: {}", format);
+    println!("Calibration coefficients: {:?}", calibration);
+
+    let mut filter = ComplementaryFilter::new(alpha);
+    let mut clock = SequenceClock::new(epoch);
+    let mut rate_hz = initial_rate_hz;
+    let mut last_tick = Instant::now();
 
     loop {
-        let measurement = Measurement::new(&mut mpu, None)?;
-        let json_meas = serde_json::to_vec(&measurement).unwrap();
+        control::poll(&udp_sender, &mut mpu, &mut rate_hz);
+
+        if let Some(hz) = rate_hz {
+            if let Ok(period) = Duration::try_from_secs_f32(1.0 / hz) {
+                let elapsed = last_tick.elapsed();
+                if elapsed < period {
+                    std::thread::sleep(period - elapsed);
+                }
+            }
+        }
+        last_tick = Instant::now();
+
+        let measurement = Measurement::new(&mut mpu, None, &mut filter, &calibration, &mut clock)?;
+        let payload = format.encode(&measurement);
         udp_sender
-            .send_to(&json_meas, udp_addr)
+            .send_to(&payload, udp_addr)
             .expect(&format!("Failed to send to UDP address: {}", udp_addr));
     }
 }
@@ -0,0 +1,33 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub struct SequenceClock {
+    seq: u64,
+    start: Instant,
+    epoch: bool,
+}
+
+impl SequenceClock {
+    pub fn new(epoch: bool) -> Self {
+        SequenceClock {
+            seq: 0,
+            start: Instant::now(),
+            epoch,
+        }
+    }
+
+    pub fn next(&mut self) -> (u64, u64) {
+        let seq = self.seq;
+        self.seq += 1;
+
+        let time = if self.epoch {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_micros() as u64
+        } else {
+            self.start.elapsed().as_micros() as u64
+        };
+
+        (seq, time)
+    }
+}
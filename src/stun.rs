@@ -0,0 +1,91 @@
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::time::Duration;
+
+use rand::Rng;
+
+const MAGIC_COOKIE: u32 = 0x2112_A442;
+const BINDING_REQUEST: u16 = 0x0001;
+const BINDING_SUCCESS_RESPONSE: u16 = 0x0101;
+const XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+pub fn discover_public_addr(
+    socket: &UdpSocket,
+    stun_server: SocketAddrV4,
+) -> std::io::Result<SocketAddrV4> {
+    let mut transaction_id = [0u8; 12];
+    rand::thread_rng().fill(&mut transaction_id);
+
+    let mut request = Vec::with_capacity(20);
+    request.extend_from_slice(&BINDING_REQUEST.to_be_bytes());
+    request.extend_from_slice(&0u16.to_be_bytes());
+    request.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+    request.extend_from_slice(&transaction_id);
+
+    socket.send_to(&request, stun_server)?;
+
+    let mut buf = [0u8; 512];
+    socket.set_read_timeout(Some(Duration::from_secs(3)))?;
+    let (len, _) = socket.recv_from(&mut buf)?;
+
+    parse_binding_response(&buf[..len], &transaction_id).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "STUN response was not a matching binding success with an XOR-MAPPED-ADDRESS",
+        )
+    })
+}
+
+// Checks the header (success type, magic cookie, echoed transaction id) before
+// trusting any attribute in the body, so a stray or spoofed datagram can't be
+// mistaken for our STUN reply.
+fn parse_binding_response(response: &[u8], transaction_id: &[u8; 12]) -> Option<SocketAddrV4> {
+    if response.len() < 20 {
+        return None;
+    }
+
+    let message_type = u16::from_be_bytes([response[0], response[1]]);
+    let cookie = u32::from_be_bytes([response[4], response[5], response[6], response[7]]);
+    if message_type != BINDING_SUCCESS_RESPONSE || cookie != MAGIC_COOKIE {
+        return None;
+    }
+    if &response[8..20] != transaction_id {
+        return None;
+    }
+
+    parse_xor_mapped_address(response)
+}
+
+fn parse_xor_mapped_address(response: &[u8]) -> Option<SocketAddrV4> {
+    let mut offset = 20;
+    while offset + 4 <= response.len() {
+        let attr_type = u16::from_be_bytes([response[offset], response[offset + 1]]);
+        let attr_len = u16::from_be_bytes([response[offset + 2], response[offset + 3]]) as usize;
+        let value_start = offset + 4;
+        let value_end = value_start + attr_len;
+        if value_end > response.len() {
+            return None;
+        }
+
+        if attr_type == XOR_MAPPED_ADDRESS && attr_len >= 8 {
+            let value = &response[value_start..value_end];
+            if value[1] != 0x01 {
+                return None; // not IPv4
+            }
+
+            let cookie_bytes = MAGIC_COOKIE.to_be_bytes();
+            let port = u16::from_be_bytes([value[2], value[3]]) ^ ((MAGIC_COOKIE >> 16) as u16);
+            let ip = Ipv4Addr::new(
+                value[4] ^ cookie_bytes[0],
+                value[5] ^ cookie_bytes[1],
+                value[6] ^ cookie_bytes[2],
+                value[7] ^ cookie_bytes[3],
+            );
+            return Some(SocketAddrV4::new(ip, port));
+        }
+
+        // attributes are padded out to a 4-byte boundary
+        offset = value_end + ((4 - (attr_len % 4)) % 4);
+    }
+
+    None
+}